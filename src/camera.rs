@@ -0,0 +1,57 @@
+use cgmath::{perspective, Deg, Matrix4, Point3, Vector3};
+
+#[rustfmt::skip]
+pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+pub struct Camera {
+    pub position: Point3<f32>,
+    pub target: Point3<f32>,
+    pub up: Vector3<f32>,
+    pub fovy: f32,
+    pub aspect: f32,
+    pub znear: f32,
+    pub zfar: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            position: Point3::new(0.0, 1.0, 2.0),
+            target: Point3::new(0.0, 0.0, 0.0),
+            up: Vector3::unit_y(),
+            fovy: 45.0,
+            aspect: 1.0,
+            znear: 0.1,
+            zfar: 100.0,
+        }
+    }
+}
+
+impl Camera {
+    pub fn view_proj(&self) -> [[f32; 4]; 4] {
+        let view = Matrix4::look_at_rh(self.position, self.target, self.up);
+        let proj = perspective(Deg(self.fovy), self.aspect, self.znear, self.zfar);
+        (OPENGL_TO_WGPU_MATRIX * proj * view).into()
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view_proj: [[f32; 4]; 4],
+    pub view_position: [f32; 4],
+}
+
+impl CameraUniform {
+    pub fn new(camera: &Camera) -> Self {
+        Self {
+            view_proj: camera.view_proj(),
+            view_position: [camera.position.x, camera.position.y, camera.position.z, 1.0],
+        }
+    }
+}
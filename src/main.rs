@@ -1,3 +1,6 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use pollster::FutureExt as _;
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
@@ -9,15 +12,63 @@ use winit::{
     window::Window,
 };
 
+mod camera;
+mod material;
+mod pass;
+mod postprocess;
+mod renderer;
+mod texture;
 mod vertices;
 
+pub use camera::Camera;
+use camera::CameraUniform;
+pub use material::{MaterialUniform, PbrMaterial, PointLight};
+use pass::OpaquePass;
+pub use postprocess::PostPassParams;
+use postprocess::{PingPongTargets, PostPass};
+pub use renderer::{Phase, RenderPass};
+use renderer::Renderer;
+pub use texture::BoundTexture;
+
 pub struct App<'window> {
-    device: Device,
-    queue: Queue,
     surface: Surface<'window>,
-    pipeline: RenderPipeline,
-    vbo: Buffer,
-    ibo: Buffer,
+    surface_config: SurfaceConfiguration,
+    color_format: TextureFormat,
+    renderer: Renderer,
+    depth_texture: Texture,
+    depth_view: Rc<RefCell<TextureView>>,
+    camera_buffers: Vec<Buffer>,
+    texture_bind_group_layout: BindGroupLayout,
+    material_bind_group_layout: BindGroupLayout,
+    scene_targets: PingPongTargets,
+    post_pass_bind_group_layout: BindGroupLayout,
+    post_pass_pipeline_layout: PipelineLayout,
+    post_pass_sampler: Sampler,
+    fullscreen_vs_shader: ShaderModule,
+    post_passes: Vec<PostPass>,
+    present_pass: PostPass,
+}
+
+const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+const FRAMES_IN_FLIGHT: usize = 2;
+
+fn create_depth_texture(device: &Device, surface_config: &SurfaceConfiguration) -> (Texture, TextureView) {
+    let depth_texture = device.create_texture(&TextureDescriptor {
+        label: Some("depth_texture"),
+        size: Extent3d {
+            width: surface_config.width,
+            height: surface_config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let depth_view = depth_texture.create_view(&TextureViewDescriptor::default());
+    (depth_texture, depth_view)
 }
 
 impl<'window> App<'window> {
@@ -40,9 +91,16 @@ impl<'window> App<'window> {
 
         let size = window.inner_size();
         let surface = inst.create_surface(window).unwrap();
-        let surface_config = surface
+        let mut surface_config = surface
             .get_default_config(&adapter, size.width, size.height)
             .unwrap();
+        // The surface format is commonly an *Srgb variant, which makes the
+        // hardware gamma-encode on store. Our shaders already do that by
+        // hand (see shader.frag), so render everywhere in the non-sRGB
+        // sibling format, and view the sRGB texture the swapchain actually
+        // owns through that non-sRGB format for the final pass.
+        let color_format = surface_config.format.remove_srgb_suffix();
+        surface_config.view_formats = vec![color_format];
         surface.configure(&device, &surface_config);
 
         let vbo = device.create_buffer_init(&BufferInitDescriptor {
@@ -63,9 +121,51 @@ impl<'window> App<'window> {
         let vs_shader = unsafe { device.create_shader_module_spirv(&vs_raw) };
         let fs_shader = unsafe { device.create_shader_module_spirv(&fs_raw) };
 
+        let camera_bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("camera_bind_group_layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        // One buffer/bind group per frame-in-flight so `update_camera` can
+        // write next frame's matrix without racing the GPU's current read.
+        let (camera_buffers, camera_bind_groups): (Vec<_>, Vec<_>) = (0..FRAMES_IN_FLIGHT)
+            .map(|_| {
+                let buffer = device.create_buffer_init(&BufferInitDescriptor {
+                    label: Some("camera_buffer"),
+                    contents: bytemuck::cast_slice(&[CameraUniform::new(&Camera::default())]),
+                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                });
+                let bind_group = device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("camera_bind_group"),
+                    layout: &camera_bind_group_layout,
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                });
+                (buffer, bind_group)
+            })
+            .unzip();
+
+        let texture_bind_group_layout = texture::create_texture_bind_group_layout(&device);
+        let material_bind_group_layout = material::create_material_bind_group_layout(&device);
+
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: None,
-            bind_group_layouts: &[],
+            bind_group_layouts: &[
+                &camera_bind_group_layout,
+                &texture_bind_group_layout,
+                &material_bind_group_layout,
+            ],
             push_constant_ranges: &[],
         });
 
@@ -76,13 +176,25 @@ impl<'window> App<'window> {
                 module: &vs_shader,
                 entry_point: "main",
                 buffers: &[VertexBufferLayout {
-                    array_stride: (std::mem::size_of::<f32>() * 3) as u64,
+                    array_stride: (std::mem::size_of::<f32>() * 8) as u64,
                     step_mode: VertexStepMode::Vertex,
-                    attributes: &[VertexAttribute {
-                        format: VertexFormat::Float32x3,
-                        offset: 0,
-                        shader_location: 0,
-                    }],
+                    attributes: &[
+                        VertexAttribute {
+                            format: VertexFormat::Float32x3,
+                            offset: 0,
+                            shader_location: 0,
+                        },
+                        VertexAttribute {
+                            format: VertexFormat::Float32x2,
+                            offset: (std::mem::size_of::<f32>() * 3) as u64,
+                            shader_location: 1,
+                        },
+                        VertexAttribute {
+                            format: VertexFormat::Float32x3,
+                            offset: (std::mem::size_of::<f32>() * 5) as u64,
+                            shader_location: 2,
+                        },
+                    ],
                 }],
             },
             primitive: PrimitiveState {
@@ -94,13 +206,19 @@ impl<'window> App<'window> {
                 polygon_mode: PolygonMode::Fill,
                 conservative: false,
             },
-            depth_stencil: None,
+            depth_stencil: Some(DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: CompareFunction::Less,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
             multisample: MultisampleState::default(),
             fragment: Some(FragmentState {
                 module: &fs_shader,
                 entry_point: "main",
                 targets: &[Some(ColorTargetState {
-                    format: surface_config.format,
+                    format: color_format,
                     blend: Some(BlendState::REPLACE),
                     write_mask: ColorWrites::all(),
                 })],
@@ -109,51 +227,212 @@ impl<'window> App<'window> {
 
         });
 
-        Self {
-            device,
-            queue,
-            surface,
+        let (depth_texture, depth_view) = create_depth_texture(&device, &surface_config);
+        let depth_view = Rc::new(RefCell::new(depth_view));
+
+        let scene_targets = PingPongTargets::new(
+            &device,
+            surface_config.width,
+            surface_config.height,
+            color_format,
+        );
+        let post_pass_bind_group_layout = postprocess::create_post_pass_bind_group_layout(&device);
+        let post_pass_sampler = postprocess::create_post_pass_sampler(&device);
+        let post_pass_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("post_pass_pipeline_layout"),
+            bind_group_layouts: &[&post_pass_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let fullscreen_vs_raw = include_spirv_raw!("./shaders/fullscreen.spv");
+        let fullscreen_vs_shader = unsafe { device.create_shader_module_spirv(&fullscreen_vs_raw) };
+        let blit_raw = include_spirv_raw!("./shaders/blit.spv");
+        let blit_shader = unsafe { device.create_shader_module_spirv(&blit_raw) };
+
+        let present_params_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("present_pass_params_buffer"),
+            contents: bytemuck::cast_slice(&[PostPassParams {
+                source_resolution: [surface_config.width as f32, surface_config.height as f32],
+                time: 0.0,
+                _padding: 0.0,
+            }]),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+        let present_pass = postprocess::create_post_pass(
+            &device,
+            &post_pass_bind_group_layout,
+            &post_pass_pipeline_layout,
+            &fullscreen_vs_shader,
+            &blit_shader,
+            color_format,
+            &post_pass_sampler,
+            &scene_targets,
+            present_params_buffer,
+        );
+
+        // A plain white texture and a neutral dielectric material so the
+        // scene pipeline has something bound at groups 1 and 2. The opaque
+        // pass keeps these bind groups: `load_texture`/`create_material`
+        // build new ones but do not swap them into it.
+        let default_texture =
+            texture::create_white_texture(&device, &queue, &texture_bind_group_layout);
+        let default_material = material::create_material(
+            &device,
+            &material_bind_group_layout,
+            MaterialUniform::new([1.0, 1.0, 1.0, 1.0], 0.0, 0.5),
+            &[PointLight {
+                position: [0.0, 2.0, 2.0, 1.0],
+                radiance: [10.0, 10.0, 10.0, 1.0],
+            }],
+        );
+
+        let mut renderer = Renderer::new(device, queue, FRAMES_IN_FLIGHT);
+        renderer.add_pass(Box::new(OpaquePass {
             pipeline,
             vbo,
             ibo,
+            index_count: vertices::INDEX_DATA.len() as u32,
+            camera_bind_groups,
+            texture_bind_group: default_texture.bind_group,
+            material_bind_group: default_material.bind_group,
+            depth_view: depth_view.clone(),
+        }));
+
+        Self {
+            surface,
+            surface_config,
+            color_format,
+            renderer,
+            depth_texture,
+            depth_view,
+            camera_buffers,
+            texture_bind_group_layout,
+            material_bind_group_layout,
+            scene_targets,
+            post_pass_bind_group_layout,
+            post_pass_pipeline_layout,
+            post_pass_sampler,
+            fullscreen_vs_shader,
+            post_passes: Vec::new(),
+            present_pass,
+        }
+    }
+
+    /// Appends a full-screen fragment pass to the post-process chain, run
+    /// after scene geometry and before the final blit to the surface.
+    /// `shader_module` is the pass's fragment stage; `params_buffer` is
+    /// whatever uniform data it reads (e.g. a `PostPassParams`). The pass
+    /// takes ownership of the buffer; `App` exposes no handle to write to it
+    /// after this call.
+    pub fn add_post_pass(&mut self, shader_module: ShaderModule, params_buffer: Buffer) {
+        let pass = postprocess::create_post_pass(
+            self.renderer.device(),
+            &self.post_pass_bind_group_layout,
+            &self.post_pass_pipeline_layout,
+            &self.fullscreen_vs_shader,
+            &shader_module,
+            self.color_format,
+            &self.post_pass_sampler,
+            &self.scene_targets,
+            params_buffer,
+        );
+        self.post_passes.push(pass);
+    }
+
+    /// Registers a custom `RenderPass` (and its `Phase`) with the renderer,
+    /// so callers can extend the render graph without editing `App::new`.
+    pub fn add_pass(&mut self, pass: Box<dyn RenderPass>) {
+        self.renderer.add_pass(pass);
+    }
+
+    pub fn update_camera(&mut self, camera: &Camera) {
+        let buffer = &self.camera_buffers[self.renderer.frame_index()];
+        self.renderer
+            .queue()
+            .write_buffer(buffer, 0, bytemuck::cast_slice(&[CameraUniform::new(camera)]));
+    }
+
+    pub fn load_texture(&self, bytes: &[u8]) -> BoundTexture {
+        texture::load_texture(
+            self.renderer.device(),
+            self.renderer.queue(),
+            &self.texture_bind_group_layout,
+            bytes,
+        )
+    }
+
+    pub fn create_material(&self, material: MaterialUniform, lights: &[PointLight]) -> PbrMaterial {
+        material::create_material(
+            self.renderer.device(),
+            &self.material_bind_group_layout,
+            material,
+            lights,
+        )
+    }
+
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
+        self.surface_config.width = new_size.width;
+        self.surface_config.height = new_size.height;
+        self.surface
+            .configure(self.renderer.device(), &self.surface_config);
+        let (depth_texture, depth_view) =
+            create_depth_texture(self.renderer.device(), &self.surface_config);
+        self.depth_texture = depth_texture;
+        *self.depth_view.borrow_mut() = depth_view;
+
+        self.scene_targets = PingPongTargets::new(
+            self.renderer.device(),
+            self.surface_config.width,
+            self.surface_config.height,
+            self.color_format,
+        );
+        self.present_pass.rebind(
+            self.renderer.device(),
+            &self.post_pass_bind_group_layout,
+            &self.post_pass_sampler,
+            &self.scene_targets,
+        );
+        for pass in &mut self.post_passes {
+            pass.rebind(
+                self.renderer.device(),
+                &self.post_pass_bind_group_layout,
+                &self.post_pass_sampler,
+                &self.scene_targets,
+            );
         }
     }
 
     pub fn render(&mut self) {
         let surface_texture = self.surface.get_current_texture().unwrap();
-        let surface_texture_view = surface_texture
-            .texture
-            .create_view(&TextureViewDescriptor::default());
-        let mut command_encoder = self
-            .device
+        // Reinterpret as the non-sRGB sibling format so the final blit's
+        // store doesn't re-encode the gamma correction the shader already
+        // applied; see the `color_format` comment in `App::new`.
+        let surface_view = surface_texture.texture.create_view(&TextureViewDescriptor {
+            format: Some(self.color_format),
+            ..Default::default()
+        });
+
+        let mut encoder = self
+            .renderer
+            .device()
             .create_command_encoder(&CommandEncoderDescriptor::default());
-        {
-            let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(RenderPassColorAttachment {
-                    view: &surface_texture_view,
-                    resolve_target: None,
-                    ops: Operations {
-                        load: LoadOp::Clear(Color {
-                            r: 0.0,
-                            g: 0.0,
-                            b: 1.0,
-                            a: 1.0,
-                        }),
-                        store: StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_vertex_buffer(0, self.vbo.slice(..));
-            render_pass.set_index_buffer(self.ibo.slice(..), IndexFormat::Uint32);
-            render_pass.draw_indexed(0..(vertices::INDEX_DATA.len() as u32), 0, 0..1);
+
+        self.renderer.record_passes(&mut encoder, &self.scene_targets.views[0]);
+
+        let mut source = 0usize;
+        for pass in &self.post_passes {
+            let target = 1 - source;
+            pass.record(&mut encoder, source, &self.scene_targets.views[target]);
+            source = target;
         }
-        self.queue.submit([command_encoder.finish()]);
+        self.present_pass.record(&mut encoder, source, &surface_view);
+
+        self.renderer.queue().submit([encoder.finish()]);
         surface_texture.present();
+        self.renderer.advance_frame();
     }
 }
 
@@ -182,6 +461,10 @@ async fn run() {
                         WindowEvent::CloseRequested => {
                             elwt.exit();
                         }
+                        WindowEvent::Resized(new_size) => {
+                            app.resize(new_size);
+                            window.request_redraw();
+                        }
                         _ => {}
                     }
                 }
@@ -0,0 +1,129 @@
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::*;
+
+pub const MAX_LIGHTS: usize = 4;
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MaterialUniform {
+    pub base_color: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub _padding: [f32; 2],
+}
+
+impl MaterialUniform {
+    pub fn new(base_color: [f32; 4], metallic: f32, roughness: f32) -> Self {
+        Self {
+            base_color,
+            metallic,
+            roughness,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PointLight {
+    pub position: [f32; 4],
+    pub radiance: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightsUniform {
+    lights: [PointLight; MAX_LIGHTS],
+    light_count: u32,
+    _padding: [u32; 3],
+}
+
+impl LightsUniform {
+    fn new(lights: &[PointLight]) -> Self {
+        let mut padded = [PointLight {
+            position: [0.0; 4],
+            radiance: [0.0; 4],
+        }; MAX_LIGHTS];
+        let count = lights.len().min(MAX_LIGHTS);
+        padded[..count].copy_from_slice(&lights[..count]);
+        Self {
+            lights: padded,
+            light_count: count as u32,
+            _padding: [0; 3],
+        }
+    }
+}
+
+pub struct PbrMaterial {
+    pub material_buffer: Buffer,
+    pub lights_buffer: Buffer,
+    pub bind_group: BindGroup,
+}
+
+pub fn create_material_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("material_bind_group_layout"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+pub fn create_material(
+    device: &Device,
+    layout: &BindGroupLayout,
+    material: MaterialUniform,
+    lights: &[PointLight],
+) -> PbrMaterial {
+    let material_buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("material_buffer"),
+        contents: bytemuck::cast_slice(&[material]),
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    });
+
+    let lights_buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("lights_buffer"),
+        contents: bytemuck::cast_slice(&[LightsUniform::new(lights)]),
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    });
+
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("material_bind_group"),
+        layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: material_buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: lights_buffer.as_entire_binding(),
+            },
+        ],
+    });
+
+    PbrMaterial {
+        material_buffer,
+        lights_buffer,
+        bind_group,
+    }
+}
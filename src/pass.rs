@@ -0,0 +1,67 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wgpu::*;
+
+use crate::renderer::{Phase, RenderPass};
+
+/// Draws the scene's opaque geometry with depth testing.
+///
+/// `depth_view` is shared with `App` so it can be swapped out in place when
+/// the surface (and therefore the depth target) is resized. `camera_bind_groups`
+/// holds one entry per frame-in-flight, each wrapping its own buffer, so a
+/// frame's `update_camera` write can't race a previous frame's in-flight draw.
+pub struct OpaquePass {
+    pub pipeline: RenderPipeline,
+    pub vbo: Buffer,
+    pub ibo: Buffer,
+    pub index_count: u32,
+    pub camera_bind_groups: Vec<BindGroup>,
+    pub texture_bind_group: BindGroup,
+    pub material_bind_group: BindGroup,
+    pub depth_view: Rc<RefCell<TextureView>>,
+}
+
+impl RenderPass for OpaquePass {
+    fn phase(&self) -> Phase {
+        Phase::Opaque
+    }
+
+    fn record(&self, encoder: &mut CommandEncoder, view: &TextureView, frame_index: usize) {
+        let depth_view = self.depth_view.borrow();
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("opaque_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color {
+                        r: 0.0,
+                        g: 0.0,
+                        b: 1.0,
+                        a: 1.0,
+                    }),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: &depth_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.camera_bind_groups[frame_index], &[]);
+        render_pass.set_bind_group(1, &self.texture_bind_group, &[]);
+        render_pass.set_bind_group(2, &self.material_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vbo.slice(..));
+        render_pass.set_index_buffer(self.ibo.slice(..), IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+}
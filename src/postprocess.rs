@@ -0,0 +1,218 @@
+use wgpu::*;
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PostPassParams {
+    pub source_resolution: [f32; 2],
+    pub time: f32,
+    pub _padding: f32,
+}
+
+/// Two same-sized, same-format color targets that post passes ping-pong
+/// between: a pass reads one and writes into the other. The underlying
+/// `Texture`s are kept alive by their `TextureView`s, so only the views
+/// need to be held here.
+pub struct PingPongTargets {
+    pub views: [TextureView; 2],
+}
+
+impl PingPongTargets {
+    pub fn new(device: &Device, width: u32, height: u32, format: TextureFormat) -> Self {
+        let make = |label| {
+            let texture = device.create_texture(&TextureDescriptor {
+                label: Some(label),
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            });
+            texture.create_view(&TextureViewDescriptor::default())
+        };
+        Self {
+            views: [make("post_process_target_a"), make("post_process_target_b")],
+        }
+    }
+}
+
+pub fn create_post_pass_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("post_pass_bind_group_layout"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension: TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 2,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+pub fn create_post_pass_sampler(device: &Device) -> Sampler {
+    device.create_sampler(&SamplerDescriptor {
+        address_mode_u: AddressMode::ClampToEdge,
+        address_mode_v: AddressMode::ClampToEdge,
+        address_mode_w: AddressMode::ClampToEdge,
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        mipmap_filter: FilterMode::Nearest,
+        ..Default::default()
+    })
+}
+
+/// One full-screen fragment pass in the post-process chain. Reads whichever
+/// `PingPongTargets` slot it was bound against and writes the other slot (or,
+/// for the final pass, the surface).
+pub struct PostPass {
+    pipeline: RenderPipeline,
+    params_buffer: Buffer,
+    bind_group_from: [BindGroup; 2],
+}
+
+fn make_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    sampler: &Sampler,
+    params_buffer: &Buffer,
+    source_view: &TextureView,
+) -> BindGroup {
+    device.create_bind_group(&BindGroupDescriptor {
+        label: Some("post_pass_bind_group"),
+        layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(source_view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(sampler),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: params_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_post_pass(
+    device: &Device,
+    bind_group_layout: &BindGroupLayout,
+    pipeline_layout: &PipelineLayout,
+    fullscreen_vs: &ShaderModule,
+    fragment_shader: &ShaderModule,
+    output_format: TextureFormat,
+    sampler: &Sampler,
+    targets: &PingPongTargets,
+    params_buffer: Buffer,
+) -> PostPass {
+    let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("post_pass_pipeline"),
+        layout: Some(pipeline_layout),
+        vertex: VertexState {
+            module: fullscreen_vs,
+            entry_point: "main",
+            buffers: &[],
+        },
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        fragment: Some(FragmentState {
+            module: fragment_shader,
+            entry_point: "main",
+            targets: &[Some(ColorTargetState {
+                format: output_format,
+                blend: Some(BlendState::REPLACE),
+                write_mask: ColorWrites::all(),
+            })],
+        }),
+        multiview: None,
+    });
+
+    let bind_group_from = [
+        make_bind_group(device, bind_group_layout, sampler, &params_buffer, &targets.views[0]),
+        make_bind_group(device, bind_group_layout, sampler, &params_buffer, &targets.views[1]),
+    ];
+
+    PostPass {
+        pipeline,
+        params_buffer,
+        bind_group_from,
+    }
+}
+
+impl PostPass {
+    /// Rebuilds the bind groups against a freshly (re)created set of
+    /// ping-pong targets, e.g. after a surface resize.
+    pub fn rebind(
+        &mut self,
+        device: &Device,
+        bind_group_layout: &BindGroupLayout,
+        sampler: &Sampler,
+        targets: &PingPongTargets,
+    ) {
+        self.bind_group_from = [
+            make_bind_group(device, bind_group_layout, sampler, &self.params_buffer, &targets.views[0]),
+            make_bind_group(device, bind_group_layout, sampler, &self.params_buffer, &targets.views[1]),
+        ];
+    }
+
+    /// `source` selects which `PingPongTargets` slot to read from (0 or 1);
+    /// `target_view` is where this pass's fullscreen triangle is drawn.
+    pub fn record(&self, encoder: &mut CommandEncoder, source: usize, target_view: &TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("post_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target_view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group_from[source], &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,70 @@
+use wgpu::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Opaque,
+    Transparent,
+    Ui,
+}
+
+pub trait RenderPass {
+    fn phase(&self) -> Phase;
+    fn record(&self, encoder: &mut CommandEncoder, view: &TextureView, frame_index: usize);
+}
+
+/// Owns the device/queue and the phase-ordered pass list, and tracks which
+/// frame-in-flight slot is current. It does not acquire or present a
+/// surface itself: `App::render` needs to route a frame through an
+/// offscreen scene target and a post-process chain before anything reaches
+/// the swapchain, which a single `Renderer`-owned target doesn't model, so
+/// `App` drives acquire/submit/present and calls `record_passes` for the
+/// scene-geometry stage of that sequence.
+pub struct Renderer {
+    device: Device,
+    queue: Queue,
+    passes: Vec<Box<dyn RenderPass>>,
+    frames_in_flight: usize,
+    frame_index: usize,
+}
+
+impl Renderer {
+    pub fn new(device: Device, queue: Queue, frames_in_flight: usize) -> Self {
+        Self {
+            device,
+            queue,
+            passes: Vec::new(),
+            frames_in_flight: frames_in_flight.max(1),
+            frame_index: 0,
+        }
+    }
+
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    pub fn queue(&self) -> &Queue {
+        &self.queue
+    }
+
+    pub fn frame_index(&self) -> usize {
+        self.frame_index
+    }
+
+    pub fn add_pass(&mut self, pass: Box<dyn RenderPass>) {
+        self.passes.push(pass);
+    }
+
+    /// Encodes every registered pass, in phase order, into `view`.
+    pub fn record_passes(&self, encoder: &mut CommandEncoder, view: &TextureView) {
+        let mut ordered: Vec<&Box<dyn RenderPass>> = self.passes.iter().collect();
+        ordered.sort_by_key(|pass| pass.phase());
+
+        for pass in ordered {
+            pass.record(encoder, view, self.frame_index);
+        }
+    }
+
+    pub fn advance_frame(&mut self) {
+        self.frame_index = (self.frame_index + 1) % self.frames_in_flight;
+    }
+}
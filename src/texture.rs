@@ -0,0 +1,124 @@
+use wgpu::*;
+
+pub struct BoundTexture {
+    pub texture: Texture,
+    pub view: TextureView,
+    pub sampler: Sampler,
+    pub bind_group: BindGroup,
+}
+
+pub fn create_texture_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("texture_bind_group_layout"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension: TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    })
+}
+
+pub fn load_texture(
+    device: &Device,
+    queue: &Queue,
+    layout: &BindGroupLayout,
+    bytes: &[u8],
+) -> BoundTexture {
+    let image = image::load_from_memory(bytes).unwrap().to_rgba8();
+    let (width, height) = image.dimensions();
+    upload_rgba(device, queue, layout, &image, width, height)
+}
+
+/// A 1x1 opaque white texture, used as the default albedo so the PBR
+/// pipeline has something bound at group 1 before a real texture is loaded.
+pub fn create_white_texture(device: &Device, queue: &Queue, layout: &BindGroupLayout) -> BoundTexture {
+    upload_rgba(device, queue, layout, &[255, 255, 255, 255], 1, 1)
+}
+
+fn upload_rgba(
+    device: &Device,
+    queue: &Queue,
+    layout: &BindGroupLayout,
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+) -> BoundTexture {
+    let size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(&TextureDescriptor {
+        label: Some("loaded_texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8UnormSrgb,
+        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    queue.write_texture(
+        ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        rgba,
+        ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * width),
+            rows_per_image: Some(height),
+        },
+        size,
+    );
+
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    let sampler = device.create_sampler(&SamplerDescriptor {
+        address_mode_u: AddressMode::ClampToEdge,
+        address_mode_v: AddressMode::ClampToEdge,
+        address_mode_w: AddressMode::ClampToEdge,
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        mipmap_filter: FilterMode::Nearest,
+        ..Default::default()
+    });
+
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("texture_bind_group"),
+        layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(&sampler),
+            },
+        ],
+    });
+
+    BoundTexture {
+        texture,
+        view,
+        sampler,
+        bind_group,
+    }
+}
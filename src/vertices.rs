@@ -0,0 +1,10 @@
+// Interleaved position (xyz) + uv (xy) + normal (xyz) for a single textured quad.
+#[rustfmt::skip]
+pub const VERTEX_DATA: &[f32] = &[
+    -0.5, -0.5, 0.0,  0.0, 1.0,  0.0, 0.0, 1.0,
+     0.5, -0.5, 0.0,  1.0, 1.0,  0.0, 0.0, 1.0,
+     0.5,  0.5, 0.0,  1.0, 0.0,  0.0, 0.0, 1.0,
+    -0.5,  0.5, 0.0,  0.0, 0.0,  0.0, 0.0, 1.0,
+];
+
+pub const INDEX_DATA: &[u32] = &[0, 1, 2, 2, 3, 0];